@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use std::env;
 use std::fs;
 use std::io::{BufRead, BufReader, BufWriter, Write};
@@ -18,6 +19,7 @@ struct CgVendor {
 }
 
 struct CgDevice {
+    vendor_id: u16,
     id: u16,
     name: String,
     subsystems: Vec<CgSubSystem>,
@@ -36,6 +38,7 @@ struct CgClass {
 }
 
 struct CgSubclass {
+    class_id: u8,
     id: u8,
     name: String,
     prog_ifs: Vec<CgProgIf>,
@@ -71,6 +74,13 @@ fn main() {
 
     let mut vendors = Map::new();
     let mut classes = Map::new();
+    let mut devices = Map::new();
+    let mut subsystems_map = Map::new();
+    let mut subclasses_map = Map::new();
+    // The same (subvendor, subdevice) pair can legitimately appear under multiple parent
+    // devices with an identical name; phf can't build a map with duplicate keys, so track
+    // which keys we've already emitted and keep only the first record for each.
+    let mut subsystem_keys = HashSet::new();
 
     for line in input.lines() {
         let line = line.unwrap();
@@ -81,7 +91,13 @@ fn main() {
         if let Ok((name, id)) = parser::vendor(&line) {
             // If there was a previous vendor, emit it.
             if let Some(vendor) = curr_vendor.take() {
-                vendors.entry(vendor.id, &quote!(#vendor).to_string());
+                emit_vendor(
+                    &vendor,
+                    &mut vendors,
+                    &mut devices,
+                    &mut subsystems_map,
+                    &mut subsystem_keys,
+                );
             }
 
             // Set our new vendor as the current vendor.
@@ -94,6 +110,7 @@ fn main() {
             // We should always have a current vendor; failure here indicates a malformed input.
             let curr_vendor = curr_vendor.as_mut().unwrap();
             curr_vendor.devices.push(CgDevice {
+                vendor_id: curr_vendor.id,
                 id,
                 name: name.into(),
                 subsystems: vec![],
@@ -118,7 +135,7 @@ fn main() {
         } else if let Ok((name, id)) = parser::class(&line) {
             // If there was a previous class, emit it.
             if let Some(class) = curr_class.take() {
-                classes.entry(class.id, &quote!(#class).to_string());
+                emit_class(&class, &mut classes, &mut subclasses_map);
             }
 
             // Set our new class as the current class.
@@ -131,6 +148,7 @@ fn main() {
             // We should always have a current class; failure here indicates a malformed input.
             let curr_class = curr_class.as_mut().unwrap();
             curr_class.subclasses.push(CgSubclass {
+                class_id: curr_class.id,
                 id,
                 name: name.into(),
                 prog_ifs: vec![],
@@ -158,10 +176,16 @@ fn main() {
         }
     }
     if let Some(vendor) = curr_vendor.take() {
-        vendors.entry(vendor.id, &quote!(#vendor).to_string());
+        emit_vendor(
+            &vendor,
+            &mut vendors,
+            &mut devices,
+            &mut subsystems_map,
+            &mut subsystem_keys,
+        );
     }
     if let Some(class) = curr_class.take() {
-        classes.entry(class.id, &quote!(#class).to_string());
+        emit_class(&class, &mut classes, &mut subclasses_map);
     }
 
     writeln!(
@@ -178,63 +202,74 @@ fn main() {
     )
     .unwrap();
 
+    writeln!(
+        output,
+        "static DEVICES: phf::Map<u32, Device> = {};",
+        devices.build()
+    )
+    .unwrap();
+
+    writeln!(
+        output,
+        "static SUBSYSTEMS: phf::Map<u32, SubSystem> = {};",
+        subsystems_map.build()
+    )
+    .unwrap();
+
+    writeln!(
+        output,
+        "static SUBCLASSES: phf::Map<u16, Subclass> = {};",
+        subclasses_map.build()
+    )
+    .unwrap();
+
     println!("cargo:rerun-if-changed=build.rs");
     println!("cargo:rerun-if-changed=pciids/pci.ids");
 }
 
-mod parser {
-    use std::num::ParseIntError;
-
-    use nom::bytes::complete::{tag, take};
-    use nom::character::complete::{hex_digit1, tab};
-    use nom::combinator::{all_consuming, map_parser, map_res};
-    use nom::sequence::{delimited, separated_pair, terminated};
-    use nom::IResult;
-
-    fn id<T, F>(size: usize, from_str_radix: F) -> impl Fn(&str) -> IResult<&str, T>
-    where
-        F: Fn(&str, u32) -> Result<T, ParseIntError>,
-    {
-        move |input| {
-            map_res(map_parser(take(size), all_consuming(hex_digit1)), |input| {
-                from_str_radix(input, 16)
-            })(input)
+// Emits a finalized vendor into the VENDORS map, plus its devices and their
+// subsystems into the flat DEVICES/SUBSYSTEMS maps so they can be looked up
+// in O(1) without going through the vendor/device they belong to.
+fn emit_vendor(
+    vendor: &CgVendor,
+    vendors: &mut Map<u16>,
+    devices: &mut Map<u32>,
+    subsystems: &mut Map<u32>,
+    subsystem_keys: &mut HashSet<u32>,
+) {
+    for device in &vendor.devices {
+        let key = ((device.vendor_id as u32) << 16) | device.id as u32;
+        devices.entry(key, &quote!(#device).to_string());
+
+        for subsystem in &device.subsystems {
+            let key = ((subsystem.subvendor as u32) << 16) | subsystem.subdevice as u32;
+            // The same (subvendor, subdevice) pair can legitimately appear under multiple
+            // devices; phf requires unique keys, so keep only the first record we see.
+            if subsystem_keys.insert(key) {
+                subsystems.entry(key, &quote!(#subsystem).to_string());
+            }
         }
     }
 
-    pub fn vendor(input: &str) -> IResult<&str, u16> {
-        let id = id(4, u16::from_str_radix);
-        terminated(id, tag("  "))(input)
-    }
-
-    pub fn device(input: &str) -> IResult<&str, u16> {
-        let id = id(4, u16::from_str_radix);
-        delimited(tab, id, tag("  "))(input)
-    }
-
-    pub fn subsystems(input: &str) -> IResult<&str, (u16, u16)> {
-        let subvendor = id(4, u16::from_str_radix);
-        let subdevice = id(4, u16::from_str_radix);
-        let id = separated_pair(subvendor, tag(" "), subdevice);
-        delimited(tag("\t\t"), id, tag("  "))(input)
-    }
-
-    pub fn class(input: &str) -> IResult<&str, u8> {
-        let id = id(2, u8::from_str_radix);
-        delimited(tag("C "), id, tag("  "))(input)
-    }
+    vendors.entry(vendor.id, &quote!(#vendor).to_string());
+}
 
-    pub fn subclass(input: &str) -> IResult<&str, u8> {
-        let id = id(2, u8::from_str_radix);
-        delimited(tab, id, tag("  "))(input)
+// Emits a finalized class into the CLASSES map, plus its subclasses into the
+// flat SUBCLASSES map so they can be looked up in O(1) without going through
+// the class they belong to.
+fn emit_class(class: &CgClass, classes: &mut Map<u8>, subclasses: &mut Map<u16>) {
+    for subclass in &class.subclasses {
+        let key = ((subclass.class_id as u16) << 8) | subclass.id as u16;
+        subclasses.entry(key, &quote!(#subclass).to_string());
     }
 
-    pub fn prog_if(input: &str) -> IResult<&str, u8> {
-        let id = id(2, u8::from_str_radix);
-        delimited(tag("\t\t"), id, tag("  "))(input)
-    }
+    classes.entry(class.id, &quote!(#class).to_string());
 }
 
+// Shared with the `runtime` feature of the library so the two parsers can never diverge.
+#[path = "src/parser.rs"]
+mod parser;
+
 impl quote::ToTokens for CgVendor {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
         let CgVendor {
@@ -243,17 +278,26 @@ impl quote::ToTokens for CgVendor {
             devices,
         } = self;
 
-        let devices = devices.iter().map(|CgDevice { id, name, subsystems }| {
-            quote! {
-                Device { vendor_id: #vendor_id, id: #id, name: #name, subsystems: &[#(#subsystems),*] }
-            }
-        });
         tokens.extend(quote! {
             Vendor { id: #vendor_id, name: #name, devices: &[#(#devices),*] }
         });
     }
 }
 
+impl quote::ToTokens for CgDevice {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        let CgDevice {
+            vendor_id,
+            id,
+            name,
+            subsystems,
+        } = self;
+        tokens.extend(quote! {
+            Device { vendor_id: #vendor_id, id: #id, name: #name, subsystems: &[#(#subsystems),*] }
+        });
+    }
+}
+
 impl quote::ToTokens for CgSubSystem {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
         let CgSubSystem {
@@ -275,17 +319,26 @@ impl quote::ToTokens for CgClass {
             subclasses,
         } = self;
 
-        let subclasses = subclasses.iter().map(|CgSubclass { id, name, prog_ifs }| {
-            quote! {
-                Subclass { class_id: #class_id, id: #id, name: #name, prog_ifs: &[#(#prog_ifs),*] }
-            }
-        });
         tokens.extend(quote! {
             Class { id: #class_id, name: #name, subclasses: &[#(#subclasses),*] }
         })
     }
 }
 
+impl quote::ToTokens for CgSubclass {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        let CgSubclass {
+            class_id,
+            id,
+            name,
+            prog_ifs,
+        } = self;
+        tokens.extend(quote! {
+            Subclass { class_id: #class_id, id: #id, name: #name, prog_ifs: &[#(#prog_ifs),*] }
+        });
+    }
+}
+
 impl quote::ToTokens for CgProgIf {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
         let CgProgIf { id, name } = self;
@@ -306,9 +359,6 @@ fn update_ids() -> Result<(), std::io::Error> {
     if status.success() {
         Ok(())
     } else {
-        Err(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            "Error fetching pci data",
-        ))
+        Err(std::io::Error::other("Error fetching pci data"))
     }
 }
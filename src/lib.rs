@@ -33,13 +33,66 @@
 //!
 //! See the individual documentation for each structure for more details.
 //!
+//! # The `runtime` feature
+//!
+//! Enabling the opt-in `runtime` feature adds [`PciIds`], which parses a `pci.ids`-formatted
+//! file (e.g. a Linux host's own `/usr/share/misc/pci.ids`) at run time instead of relying
+//! solely on the database vendored at compile time. It implements the same [`PciDatabase`]
+//! trait as the compiled-in database, so lookup code can be written once and run against
+//! either.
+//!
+//! [`parser`](crate::parser) (shared with `build.rs`) depends on `nom`, pulled in as a
+//! normal, optional dependency gated behind `runtime = ["dep:nom"]` in `Cargo.toml`.
+//! [`PciDatabase`]'s use of return-position `impl Trait in traits` requires Rust 1.75,
+//! pinned via `rust-version` in the manifest.
+//!
 
-#![no_std]
+#![cfg_attr(not(feature = "runtime"), no_std)]
 #![warn(missing_docs)]
 
-// Codegen: introduces VENDORS, a phf::Map<u16, Vendor>.
+// The `runtime` feature parses a caller-supplied database at run time using the same
+// line-matching logic `build.rs` uses at compile time; it needs `alloc`/`std`, hence the
+// `no_std` opt-out above.
+#[cfg(feature = "runtime")]
+mod parser;
+#[cfg(feature = "runtime")]
+mod runtime;
+#[cfg(feature = "runtime")]
+pub use runtime::{ParseError, PciIds};
+
+// Codegen: introduces VENDORS/CLASSES/DEVICES/SUBSYSTEMS/SUBCLASSES phf::Maps.
 include!(concat!(env!("OUT_DIR"), "/pci_ids.cg.rs"));
 
+// Packs a (vendor, device) or (subvendor, subdevice) ID pair into the flat
+// key space used by the DEVICES and SUBSYSTEMS maps.
+fn device_key(hi: u16, lo: u16) -> u32 {
+    ((hi as u32) << 16) | lo as u32
+}
+
+// Packs a (class, subclass) ID pair into the flat key space used by the
+// SUBCLASSES map.
+fn subclass_key(cid: u8, sid: u8) -> u16 {
+    ((cid as u16) << 8) | sid as u16
+}
+
+// Case-insensitive ASCII substring search, written by hand (rather than via e.g.
+// `to_ascii_lowercase`) so name search stays available without `alloc`.
+fn contains_ignore_ascii_case(haystack: &str, needle: &str) -> bool {
+    let haystack = haystack.as_bytes();
+    let needle = needle.as_bytes();
+
+    if needle.is_empty() {
+        return true;
+    }
+    if needle.len() > haystack.len() {
+        return false;
+    }
+
+    haystack
+        .windows(needle.len())
+        .any(|window| window.eq_ignore_ascii_case(needle))
+}
+
 /// An abstraction for iterating over all vendors in the PCI database.
 pub struct Vendors;
 impl Vendors {
@@ -47,6 +100,29 @@ impl Vendors {
     pub fn iter() -> impl Iterator<Item = &'static Vendor> {
         VENDORS.values()
     }
+
+    /// Returns an iterator over all vendors whose name contains `substring`, matched
+    /// case-insensitively (ASCII only).
+    pub fn search<'a>(substring: &'a str) -> impl Iterator<Item = &'static Vendor> + 'a {
+        Vendors::iter().filter(move |vendor| contains_ignore_ascii_case(vendor.name(), substring))
+    }
+}
+
+/// An abstraction for doing a reverse, name-based search over every device in the PCI
+/// database, independent of any particular vendor.
+pub struct Devices;
+impl Devices {
+    /// Returns an iterator over every `(vendor, device)` pair in the database whose device
+    /// name contains `substring`, matched case-insensitively (ASCII only).
+    pub fn search<'a>(
+        substring: &'a str,
+    ) -> impl Iterator<Item = (&'static Vendor, &'static Device)> + 'a {
+        Vendors::iter().flat_map(move |vendor| {
+            vendor
+                .search_devices(substring)
+                .map(move |device| (vendor, device))
+        })
+    }
 }
 
 /// Represents a PCI device vendor in the PCI database.
@@ -75,6 +151,16 @@ impl Vendor {
     pub fn devices(&self) -> impl Iterator<Item = &'static Device> {
         self.devices.iter()
     }
+
+    /// Returns an iterator over the vendor's devices whose name contains `substring`,
+    /// matched case-insensitively (ASCII only).
+    pub fn search_devices<'a>(
+        &self,
+        substring: &'a str,
+    ) -> impl Iterator<Item = &'static Device> + 'a {
+        self.devices()
+            .filter(move |device| contains_ignore_ascii_case(device.name(), substring))
+    }
 }
 
 /// Represents a single device in the PCI database.
@@ -92,10 +178,10 @@ pub struct Device {
 impl Device {
     /// Returns the [`Device`] corresponding to the given vendor and product IDs,
     /// or `None` if no such device exists in the DB.
+    ///
+    /// This is a direct `O(1)` lookup rather than a per-vendor scan.
     pub fn from_vid_pid(vid: u16, pid: u16) -> Option<&'static Device> {
-        let vendor = Vendor::from_id(vid);
-
-        vendor.and_then(|v| v.devices().find(|d| d.id == pid))
+        DEVICES.get(&device_key(vid, pid))
     }
 
     /// Returns the [`Vendor`] that this device belongs to.
@@ -147,6 +233,17 @@ pub struct SubSystem {
 }
 
 impl SubSystem {
+    /// Returns the [`SubSystem`] corresponding to the given subvendor and subdevice IDs,
+    /// or `None` if no such subsystem exists in the DB.
+    ///
+    /// **NOTE**: The same subvendor/subdevice pair can legitimately appear under multiple
+    /// parent devices with an identical name, so this returns *a* matching subsystem record,
+    /// not necessarily the one attached to a particular [`Device`]. Use [`Device::subsystems`]
+    /// for the parent-scoped view.
+    pub fn from_ids(subvendor: u16, subdevice: u16) -> Option<&'static SubSystem> {
+        SUBSYSTEMS.get(&device_key(subvendor, subdevice))
+    }
+
     /// Returns the subsystem's ID.
     pub fn subvendor(&self) -> u16 {
         self.subvendor
@@ -161,6 +258,21 @@ impl SubSystem {
     pub fn name(&self) -> &'static str {
         self.name
     }
+
+    /// Returns the [`Vendor`] that this subsystem's subvendor ID corresponds to, or `None`
+    /// if the subvendor is not itself a known vendor in the DB.
+    ///
+    /// This resolves, e.g., the card manufacturer of a subsystem whose parent device
+    /// belongs to a different vendor (a card vendor's product built on another vendor's chip).
+    pub fn vendor(&self) -> Option<&'static Vendor> {
+        Vendor::from_id(self.subvendor)
+    }
+
+    /// Returns the [`Device`] that this subsystem's subvendor/subdevice IDs correspond to,
+    /// or `None` if no such device exists in the DB.
+    pub fn subdevice_device(&self) -> Option<&'static Device> {
+        Device::from_vid_pid(self.subvendor, self.subdevice)
+    }
 }
 
 /// An abstraction for iterating over all classes in the PCI database.
@@ -213,10 +325,10 @@ pub struct Subclass {
 
 impl Subclass {
     /// Returns the [`Subclass`] corresponding to the given class and subclass IDs, or `None` if no such device exists in the DB.
+    ///
+    /// This is a direct `O(1)` lookup rather than a per-class scan.
     pub fn from_cid_sid(cid: u8, sid: u8) -> Option<&'static Self> {
-        let class = Class::from_id(cid);
-
-        class.and_then(|c| c.subclasses().find(|s| s.id == sid))
+        SUBCLASSES.get(&subclass_key(cid, sid))
     }
 
     /// Returns the [`Class`] that this subclass belongs to.
@@ -276,6 +388,138 @@ impl ProgIf {
     }
 }
 
+/// Resolves raw PCI config-space fields against the PCI database in one pass, returning a
+/// [`PciDescriptor`] that bundles every matching record a PCI enumerator would otherwise have
+/// to look up by hand (vendor, device, subsystem, class, subclass, and programming interface).
+pub fn describe(
+    vid: u16,
+    pid: u16,
+    subvendor: u16,
+    subdevice: u16,
+    class: u8,
+    subclass: u8,
+    prog_if: u8,
+) -> PciDescriptor {
+    let subclass_entry = Subclass::from_cid_sid(class, subclass);
+
+    PciDescriptor {
+        vid,
+        pid,
+        subvendor,
+        subdevice,
+        class,
+        subclass,
+        prog_if,
+        vendor: Vendor::from_id(vid),
+        device: Device::from_vid_pid(vid, pid),
+        subsystem: SubSystem::from_ids(subvendor, subdevice),
+        class_entry: Class::from_id(class),
+        subclass_entry,
+        prog_if_entry: subclass_entry.and_then(|s| s.prog_ifs().find(|p| p.id() == prog_if)),
+    }
+}
+
+/// The resolved PCI database records for a device identified by its raw config-space fields,
+/// as produced by [`describe`].
+///
+/// Its [`Display`](core::fmt::Display) impl renders the conventional `lspci`-style summary
+/// line, substituting an `Unknown ... 0x....`-style placeholder for an absent vendor or
+/// device, for a class absent from the database, and for a nonzero subsystem pair or prog-if
+/// absent from it. An absent subclass of a known class is shown by the class name; an
+/// all-zero subsystem pair or a zero prog-if that is absent is left out of the line.
+#[derive(Clone, Copy, Debug)]
+pub struct PciDescriptor {
+    vid: u16,
+    pid: u16,
+    subvendor: u16,
+    subdevice: u16,
+    class: u8,
+    subclass: u8,
+    prog_if: u8,
+    vendor: Option<&'static Vendor>,
+    device: Option<&'static Device>,
+    subsystem: Option<&'static SubSystem>,
+    class_entry: Option<&'static Class>,
+    subclass_entry: Option<&'static Subclass>,
+    prog_if_entry: Option<&'static ProgIf>,
+}
+
+impl PciDescriptor {
+    /// Returns the resolved [`Vendor`], or `None` if `vid` is absent from the database.
+    pub fn vendor(&self) -> Option<&'static Vendor> {
+        self.vendor
+    }
+
+    /// Returns the resolved [`Device`], or `None` if the `(vid, pid)` pair is absent from the database.
+    pub fn device(&self) -> Option<&'static Device> {
+        self.device
+    }
+
+    /// Returns the matching [`SubSystem`], or `None` if the `(subvendor, subdevice)` pair is
+    /// absent from the database.
+    pub fn subsystem(&self) -> Option<&'static SubSystem> {
+        self.subsystem
+    }
+
+    /// Returns the resolved [`Class`], or `None` if `class` is absent from the database.
+    pub fn class(&self) -> Option<&'static Class> {
+        self.class_entry
+    }
+
+    /// Returns the resolved [`Subclass`], or `None` if the `(class, subclass)` pair is absent
+    /// from the database.
+    pub fn subclass(&self) -> Option<&'static Subclass> {
+        self.subclass_entry
+    }
+
+    /// Returns the resolved [`ProgIf`], or `None` if the `(class, subclass, prog_if)` triple is
+    /// absent from the database.
+    pub fn prog_if(&self) -> Option<&'static ProgIf> {
+        self.prog_if_entry
+    }
+}
+
+impl core::fmt::Display for PciDescriptor {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.subclass_entry.map(|s| s.name()).or(self.class_entry.map(|c| c.name())) {
+            Some(name) => write!(f, "{name}: ")?,
+            None => write!(
+                f,
+                "Unknown class {:#06x}: ",
+                subclass_key(self.class, self.subclass)
+            )?,
+        }
+
+        match self.vendor {
+            Some(vendor) => write!(f, "{}", vendor.name())?,
+            None => write!(f, "Unknown vendor {:#06x}", self.vid)?,
+        }
+
+        match self.device {
+            Some(device) => write!(f, " {}", device.name())?,
+            None => write!(f, " Unknown device {:#06x}", self.pid)?,
+        }
+
+        if let Some(subsystem) = self.subsystem {
+            write!(f, " ({})", subsystem.name())?;
+        } else if self.subvendor != 0 || self.subdevice != 0 {
+            write!(
+                f,
+                " (Unknown subsystem {:#06x}:{:#06x})",
+                self.subvendor, self.subdevice
+            )?;
+        }
+
+        if let Some(prog_if) = self.prog_if_entry {
+            write!(f, " [{}]", prog_if.name())?;
+        } else if self.prog_if != 0 {
+            write!(f, " [Unknown prog-if {:#04x}]", self.prog_if)?;
+        }
+
+        Ok(())
+    }
+}
+
 /// A convenience trait for retrieving a top-level entity (like a [`Vendor`]) from the PCI
 /// database by its unique ID.
 pub trait FromId<T> {
@@ -295,6 +539,73 @@ impl FromId<u8> for Class {
     }
 }
 
+/// A source of PCI ID data — either the database vendored into this crate at compile time
+/// ([`StaticDatabase`]), or, with the `runtime` feature enabled, one parsed from a
+/// caller-supplied `pci.ids` file ([`PciIds`](crate::PciIds)).
+///
+/// Lets code that looks up vendors, devices, classes, and subclasses be written once and
+/// run against either source.
+///
+/// **NOTE**: This trait returns `impl Iterator` from its methods (return-position impl
+/// Trait in traits), which requires Rust 1.75 or newer. The crate's minimum supported Rust
+/// version must be pinned to at least that in `Cargo.toml`.
+pub trait PciDatabase {
+    /// Returns an iterator over all vendors in the database.
+    fn vendors(&self) -> impl Iterator<Item = &Vendor>;
+
+    /// Returns an iterator over all classes in the database.
+    fn classes(&self) -> impl Iterator<Item = &Class>;
+
+    /// Returns the vendor with the given ID, or `None` if none exists.
+    fn vendor(&self, id: u16) -> Option<&Vendor>;
+
+    /// Returns the device with the given vendor/product ID pair, or `None` if none exists.
+    fn device(&self, vid: u16, pid: u16) -> Option<&Device>;
+
+    /// Returns the class with the given ID, or `None` if none exists.
+    fn class(&self, id: u8) -> Option<&Class>;
+
+    /// Returns the subclass with the given class/subclass ID pair, or `None` if none exists.
+    fn subclass(&self, cid: u8, sid: u8) -> Option<&Subclass>;
+}
+
+/// A [`PciDatabase`] backed by the database vendored into this crate at compile time.
+///
+/// This is a zero-sized handle onto the same static tables used by [`Vendors`], [`Classes`],
+/// and the `FromId`-based lookups; construct it to pass the compiled-in database anywhere an
+/// `impl PciDatabase` (or a generic `T: PciDatabase`) is expected alongside a runtime-loaded
+/// one. The trait cannot be used as `dyn PciDatabase`, since its methods return `impl Iterator`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StaticDatabase;
+
+impl PciDatabase for StaticDatabase {
+    fn vendors(&self) -> impl Iterator<Item = &Vendor> {
+        // Reborrow down from `&'static` to the lifetime the trait ties to `&self`; the two
+        // aren't the same type to the type checker, so this can't be left as a plain return.
+        Vendors::iter().map(|vendor| vendor as &Vendor)
+    }
+
+    fn classes(&self) -> impl Iterator<Item = &Class> {
+        Classes::iter().map(|class| class as &Class)
+    }
+
+    fn vendor(&self, id: u16) -> Option<&Vendor> {
+        Vendor::from_id(id)
+    }
+
+    fn device(&self, vid: u16, pid: u16) -> Option<&Device> {
+        Device::from_vid_pid(vid, pid)
+    }
+
+    fn class(&self, id: u8) -> Option<&Class> {
+        Class::from_id(id)
+    }
+
+    fn subclass(&self, cid: u8, sid: u8) -> Option<&Subclass> {
+        Subclass::from_cid_sid(cid, sid)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -366,4 +677,117 @@ mod tests {
 
         assert_eq!(subclass, subclass2);
     }
+
+    #[test]
+    fn test_subsystem_vendor_and_subdevice_device() {
+        let subsystem = Vendors::iter()
+            .flat_map(|vendor| vendor.devices())
+            .flat_map(|device| device.subsystems())
+            .find(|subsystem| subsystem.vendor().is_some())
+            .expect("expected at least one subsystem whose subvendor is a known vendor");
+
+        let vendor = subsystem.vendor().unwrap();
+        assert_eq!(vendor.id(), subsystem.subvendor());
+
+        if let Some(device) = subsystem.subdevice_device() {
+            assert_eq!(
+                device.as_vid_pid(),
+                (subsystem.subvendor(), subsystem.subdevice())
+            );
+        }
+    }
+
+    // `format!`/`to_string()` need `alloc`, which is only available under the `runtime`
+    // feature (the crate is `no_std` otherwise), so these are gated along with it.
+    #[cfg(feature = "runtime")]
+    #[test]
+    fn test_describe_and_display_resolved() {
+        let vendor = Vendor::from_id(0x16ae).unwrap();
+        let device = Device::from_vid_pid(0x16ae, 0x000a).unwrap();
+        let subclass = Subclass::from_cid_sid(0x07, 0x00).unwrap();
+
+        // 0xff isn't one of the serial controller subclass's declared prog-ifs, so it
+        // resolves to `None`, and the rendered line carries the "Unknown prog-if" suffix.
+        let descriptor = describe(0x16ae, 0x000a, 0, 0, 0x07, 0x00, 0xff);
+
+        assert_eq!(descriptor.vendor(), Some(vendor));
+        assert_eq!(descriptor.device(), Some(device));
+        assert_eq!(descriptor.subclass(), Some(subclass));
+        assert_eq!(descriptor.subsystem(), None);
+        assert_eq!(descriptor.prog_if(), None);
+
+        let expected = format!(
+            "{}: {} {} [Unknown prog-if 0xff]",
+            subclass.name(),
+            vendor.name(),
+            device.name()
+        );
+        assert_eq!(descriptor.to_string(), expected);
+    }
+
+    #[cfg(feature = "runtime")]
+    #[test]
+    fn test_describe_and_display_unknown() {
+        // Search for IDs the database really lacks rather than assuming all-ones IDs are
+        // absent; with no such vendor, no (vid, pid) device can be found either.
+        let vid = (0..=u16::MAX)
+            .rev()
+            .find(|&id| Vendor::from_id(id).is_none())
+            .unwrap();
+        let subvendor = (0..=u16::MAX)
+            .rev()
+            .find(|&id| SubSystem::from_ids(id, 0xffff).is_none())
+            .unwrap();
+        let descriptor = describe(vid, 0xffff, subvendor, 0xffff, 0xfe, 0xfe, 0xff);
+
+        assert_eq!(descriptor.vendor(), None);
+        assert_eq!(descriptor.device(), None);
+        assert_eq!(descriptor.class(), None);
+        assert_eq!(descriptor.subclass(), None);
+        assert_eq!(descriptor.subsystem(), None);
+        assert_eq!(descriptor.prog_if(), None);
+
+        assert_eq!(
+            descriptor.to_string(),
+            format!(
+                "Unknown class 0xfefe: Unknown vendor {vid:#06x} Unknown device 0xffff \
+                 (Unknown subsystem {subvendor:#06x}:0xffff) [Unknown prog-if 0xff]"
+            )
+        );
+    }
+
+    #[test]
+    fn test_contains_ignore_ascii_case() {
+        assert!(contains_ignore_ascii_case("SafeXcel 1841", "safexcel"));
+        assert!(contains_ignore_ascii_case("SafeXcel 1841", "1841"));
+        assert!(contains_ignore_ascii_case("SafeXcel 1841", ""));
+        assert!(!contains_ignore_ascii_case("SafeXcel 1841", "zzz"));
+        assert!(!contains_ignore_ascii_case("SafeXcel 1841", "SafeXcel 18411"));
+    }
+
+    #[test]
+    fn test_vendors_search() {
+        let vendor = Vendors::search("mediatek")
+            .find(|vendor| vendor.id() == 0x14c3)
+            .unwrap();
+        assert_eq!(vendor.name(), "MEDIATEK Corp.");
+
+        assert!(Vendors::search("definitely-not-a-real-vendor-name")
+            .next()
+            .is_none());
+    }
+
+    #[test]
+    fn test_devices_search() {
+        let (vendor, device) = Devices::search("SafeXcel 1841")
+            .find(|(_, device)| device.id() == 0x000a)
+            .unwrap();
+
+        assert_eq!(vendor.id(), 0x16ae);
+        assert_eq!(device.name(), "SafeXcel 1841");
+
+        assert!(Devices::search("definitely-not-a-real-device-name")
+            .next()
+            .is_none());
+    }
 }
@@ -0,0 +1,61 @@
+//! Line-matching parser for the `pci.ids` database format.
+//!
+//! This module is shared between `build.rs` (which uses it to parse the vendored
+//! database at compile time) and the `runtime` feature (which uses the exact same
+//! logic to parse a caller-supplied database at run time), so the two can never
+//! silently diverge.
+//!
+//! "Parser" is in scare-quotes because it's really a line matcher with a small
+//! amount of context needed for pairing nested entities (e.g. devices) with their
+//! parents (e.g. vendors).
+
+use core::num::ParseIntError;
+
+use nom::bytes::complete::{tag, take};
+use nom::character::complete::{hex_digit1, tab};
+use nom::combinator::{all_consuming, map_parser, map_res};
+use nom::sequence::{delimited, separated_pair, terminated};
+use nom::IResult;
+
+fn id<T, F>(size: usize, from_str_radix: F) -> impl Fn(&str) -> IResult<&str, T>
+where
+    F: Fn(&str, u32) -> Result<T, ParseIntError>,
+{
+    move |input| {
+        map_res(map_parser(take(size), all_consuming(hex_digit1)), |input| {
+            from_str_radix(input, 16)
+        })(input)
+    }
+}
+
+pub(crate) fn vendor(input: &str) -> IResult<&str, u16> {
+    let id = id(4, u16::from_str_radix);
+    terminated(id, tag("  "))(input)
+}
+
+pub(crate) fn device(input: &str) -> IResult<&str, u16> {
+    let id = id(4, u16::from_str_radix);
+    delimited(tab, id, tag("  "))(input)
+}
+
+pub(crate) fn subsystems(input: &str) -> IResult<&str, (u16, u16)> {
+    let subvendor = id(4, u16::from_str_radix);
+    let subdevice = id(4, u16::from_str_radix);
+    let id = separated_pair(subvendor, tag(" "), subdevice);
+    delimited(tag("\t\t"), id, tag("  "))(input)
+}
+
+pub(crate) fn class(input: &str) -> IResult<&str, u8> {
+    let id = id(2, u8::from_str_radix);
+    delimited(tag("C "), id, tag("  "))(input)
+}
+
+pub(crate) fn subclass(input: &str) -> IResult<&str, u8> {
+    let id = id(2, u8::from_str_radix);
+    delimited(tab, id, tag("  "))(input)
+}
+
+pub(crate) fn prog_if(input: &str) -> IResult<&str, u8> {
+    let id = id(2, u8::from_str_radix);
+    delimited(tag("\t\t"), id, tag("  "))(input)
+}
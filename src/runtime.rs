@@ -0,0 +1,370 @@
+//! Runtime loading of a `pci.ids`-formatted database, gated behind the `runtime` feature.
+//!
+//! Everything else in this crate is vendored at compile time, which means a consumer on
+//! a Linux host can never pick up the system's own, possibly newer, copy of the database.
+//! [`PciIds`] fills that gap by parsing a database at run time into owned tables, using
+//! the exact same [`parser`](crate::parser) logic as `build.rs` so the two can't diverge.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+use crate::{device_key, parser, subclass_key};
+use crate::{Class, Device, PciDatabase, ProgIf, SubSystem, Subclass, Vendor};
+
+/// The well-known locations of the system `pci.ids` database on Linux hosts, tried in order
+/// by [`PciIds::from_system_path`].
+const SYSTEM_PATHS: &[&str] = &[
+    "/usr/share/misc/pci.ids",
+    "/usr/share/hwdata/pci.ids",
+    "/share/misc/pci.ids",
+];
+
+/// An error encountered while loading or parsing a `pci.ids`-formatted database.
+#[derive(Debug)]
+pub enum ParseError {
+    /// An I/O error occurred while reading the source.
+    Io(std::io::Error),
+    /// A line appeared out of the order the `pci.ids` format requires, e.g. a device or
+    /// subsystem line before any vendor line.
+    UnexpectedLine(String),
+    /// None of the well-known system paths contain a `pci.ids` database.
+    NotFound,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Io(e) => write!(f, "I/O error reading pci.ids: {e}"),
+            ParseError::UnexpectedLine(line) => {
+                write!(f, "unexpected pci.ids line out of order: {line:?}")
+            }
+            ParseError::NotFound => {
+                write!(f, "no pci.ids database found at any known system path")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl From<std::io::Error> for ParseError {
+    fn from(e: std::io::Error) -> Self {
+        ParseError::Io(e)
+    }
+}
+
+fn leak_str(s: String) -> &'static str {
+    Box::leak(s.into_boxed_str())
+}
+
+fn leak_slice<T>(v: Vec<T>) -> &'static [T] {
+    Box::leak(v.into_boxed_slice())
+}
+
+// Staging types mirroring build.rs's CgVendor/CgDevice/CgClass/CgSubclass: a vendor or class
+// accumulates its children across subsequent lines and is only turned into a final, leaked
+// `Vendor`/`Class` once the next entry of the same kind (or EOF) shows it's complete.
+struct RtVendor {
+    id: u16,
+    name: String,
+    devices: Vec<RtDevice>,
+}
+
+struct RtDevice {
+    vendor_id: u16,
+    id: u16,
+    name: String,
+    subsystems: Vec<SubSystem>,
+}
+
+struct RtClass {
+    id: u8,
+    name: String,
+    subclasses: Vec<RtSubclass>,
+}
+
+struct RtSubclass {
+    class_id: u8,
+    id: u8,
+    name: String,
+    prog_ifs: Vec<ProgIf>,
+}
+
+/// A PCI ID database parsed at run time from a `pci.ids`-formatted source, as an alternative
+/// to the database vendored into the crate at compile time.
+///
+/// Implements [`PciDatabase`] so code that looks up vendors, devices, classes, and subclasses
+/// can be written once and run against either this or the compiled-in database.
+///
+/// **NOTE**: To hand out the same `&'static str`/`&'static [T]` types the compiled-in database
+/// uses, every name and slice parsed here is leaked (never freed) for the life of the process.
+/// Construct one `PciIds` per process rather than re-parsing on a timer or retry loop.
+#[derive(Debug, Default)]
+pub struct PciIds {
+    vendors: HashMap<u16, Vendor>,
+    classes: HashMap<u8, Class>,
+    devices: HashMap<u32, Device>,
+    subclasses: HashMap<u16, Subclass>,
+}
+
+impl PciIds {
+    /// Parses a `pci.ids`-formatted database from `reader`.
+    ///
+    /// Uses the exact same line-matching logic as the database vendored at compile time
+    /// (see `build.rs`). Unlike `build.rs`, which stops at the first line it does not
+    /// recognize, this skips such lines and goes on parsing the rest of the input.
+    pub fn from_reader(reader: impl BufRead) -> Result<PciIds, ParseError> {
+        let mut db = PciIds::default();
+
+        let mut curr_vendor: Option<RtVendor> = None;
+        let mut curr_device_id = 0u16;
+        let mut curr_class: Option<RtClass> = None;
+        let mut curr_subclass_id = 0u8;
+
+        for line in reader.lines() {
+            let line = line?;
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Ok((name, id)) = parser::vendor(&line) {
+                if let Some(vendor) = curr_vendor.take() {
+                    finalize_vendor(vendor, &mut db);
+                }
+                curr_vendor = Some(RtVendor {
+                    id,
+                    name: name.into(),
+                    devices: vec![],
+                });
+            } else if let Ok((name, id)) = parser::device(&line) {
+                let vendor = curr_vendor
+                    .as_mut()
+                    .ok_or_else(|| ParseError::UnexpectedLine(line.clone()))?;
+                vendor.devices.push(RtDevice {
+                    vendor_id: vendor.id,
+                    id,
+                    name: name.into(),
+                    subsystems: vec![],
+                });
+                curr_device_id = id;
+            } else if let Ok((name, (subvendor, subdevice))) = parser::subsystems(&line) {
+                let vendor = curr_vendor
+                    .as_mut()
+                    .ok_or_else(|| ParseError::UnexpectedLine(line.clone()))?;
+                let device = vendor
+                    .devices
+                    .iter_mut()
+                    .find(|d| d.id == curr_device_id)
+                    .ok_or_else(|| ParseError::UnexpectedLine(line.clone()))?;
+                device.subsystems.push(SubSystem {
+                    subvendor,
+                    subdevice,
+                    name: leak_str(name.into()),
+                });
+            } else if let Ok((name, id)) = parser::class(&line) {
+                if let Some(class) = curr_class.take() {
+                    finalize_class(class, &mut db);
+                }
+                curr_class = Some(RtClass {
+                    id,
+                    name: name.into(),
+                    subclasses: vec![],
+                });
+            } else if let Ok((name, id)) = parser::subclass(&line) {
+                let class = curr_class
+                    .as_mut()
+                    .ok_or_else(|| ParseError::UnexpectedLine(line.clone()))?;
+                class.subclasses.push(RtSubclass {
+                    class_id: class.id,
+                    id,
+                    name: name.into(),
+                    prog_ifs: vec![],
+                });
+                curr_subclass_id = id;
+            } else if let Ok((name, id)) = parser::prog_if(&line) {
+                let class = curr_class
+                    .as_mut()
+                    .ok_or_else(|| ParseError::UnexpectedLine(line.clone()))?;
+                let subclass = class
+                    .subclasses
+                    .iter_mut()
+                    .find(|s| s.id == curr_subclass_id)
+                    .ok_or_else(|| ParseError::UnexpectedLine(line.clone()))?;
+                subclass.prog_ifs.push(ProgIf {
+                    id,
+                    name: leak_str(name.into()),
+                });
+            } else {
+                // Lots of other things that could be parsed out (language, dialect, country
+                // code, HID types, ...); see the matching TODO in build.rs. Unlike build.rs's
+                // trusted, vendored input, a runtime-supplied file may legitimately contain
+                // such trailing sections, so skip rather than truncating the rest of the DB.
+                continue;
+            }
+        }
+
+        if let Some(vendor) = curr_vendor.take() {
+            finalize_vendor(vendor, &mut db);
+        }
+        if let Some(class) = curr_class.take() {
+            finalize_class(class, &mut db);
+        }
+
+        Ok(db)
+    }
+
+    /// Loads the database from the first of the well-known system `pci.ids` locations that
+    /// exists, for hosts (typically Linux) that maintain their own, potentially newer, copy.
+    pub fn from_system_path() -> Result<PciIds, ParseError> {
+        for path in SYSTEM_PATHS {
+            if let Ok(file) = File::open(path) {
+                return PciIds::from_reader(BufReader::new(file));
+            }
+        }
+        Err(ParseError::NotFound)
+    }
+
+    /// Returns an iterator over all vendors in the database.
+    pub fn vendors(&self) -> impl Iterator<Item = &Vendor> {
+        self.vendors.values()
+    }
+
+    /// Returns an iterator over all classes in the database.
+    pub fn classes(&self) -> impl Iterator<Item = &Class> {
+        self.classes.values()
+    }
+}
+
+fn finalize_vendor(vendor: RtVendor, db: &mut PciIds) {
+    let devices: Vec<Device> = vendor
+        .devices
+        .into_iter()
+        .map(|device| {
+            let subsystems = leak_slice(device.subsystems);
+            let device = Device {
+                vendor_id: device.vendor_id,
+                id: device.id,
+                name: leak_str(device.name),
+                subsystems,
+            };
+            db.devices.insert(device_key(device.vendor_id, device.id), device);
+            device
+        })
+        .collect();
+
+    let vendor = Vendor {
+        id: vendor.id,
+        name: leak_str(vendor.name),
+        devices: leak_slice(devices),
+    };
+    db.vendors.insert(vendor.id, vendor);
+}
+
+fn finalize_class(class: RtClass, db: &mut PciIds) {
+    let subclasses: Vec<Subclass> = class
+        .subclasses
+        .into_iter()
+        .map(|subclass| {
+            let prog_ifs = leak_slice(subclass.prog_ifs);
+            let subclass = Subclass {
+                class_id: subclass.class_id,
+                id: subclass.id,
+                name: leak_str(subclass.name),
+                prog_ifs,
+            };
+            db.subclasses
+                .insert(subclass_key(subclass.class_id, subclass.id), subclass);
+            subclass
+        })
+        .collect();
+
+    let class = Class {
+        id: class.id,
+        name: leak_str(class.name),
+        subclasses: leak_slice(subclasses),
+    };
+    db.classes.insert(class.id, class);
+}
+
+impl PciDatabase for PciIds {
+    fn vendors(&self) -> impl Iterator<Item = &Vendor> {
+        PciIds::vendors(self)
+    }
+
+    fn classes(&self) -> impl Iterator<Item = &Class> {
+        PciIds::classes(self)
+    }
+
+    fn vendor(&self, id: u16) -> Option<&Vendor> {
+        self.vendors.get(&id)
+    }
+
+    fn device(&self, vid: u16, pid: u16) -> Option<&Device> {
+        self.devices.get(&device_key(vid, pid))
+    }
+
+    fn class(&self, id: u8) -> Option<&Class> {
+        self.classes.get(&id)
+    }
+
+    fn subclass(&self, cid: u8, sid: u8) -> Option<&Subclass> {
+        self.subclasses.get(&subclass_key(cid, sid))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "14c3  MEDIATEK Corp.\n\
+        \t1000  Some Device\n\
+        \t\t1043 8461  ASUS Some Card\n\
+        C 07  Communication controller\n\
+        \t00  Serial controller\n\
+        \t\t00  8250\n";
+
+    #[test]
+    fn test_from_reader_round_trip() {
+        let db = PciIds::from_reader(SAMPLE.as_bytes()).unwrap();
+
+        let vendor = db.vendor(0x14c3).unwrap();
+        assert_eq!(vendor.name(), "MEDIATEK Corp.");
+
+        let device = db.device(0x14c3, 0x1000).unwrap();
+        assert_eq!(device.name(), "Some Device");
+
+        let subsystem = device.subsystems().next().unwrap();
+        assert_eq!(subsystem.name(), "ASUS Some Card");
+        assert_eq!(subsystem.subvendor(), 0x1043);
+        assert_eq!(subsystem.subdevice(), 0x8461);
+
+        let class = db.class(0x07).unwrap();
+        assert_eq!(class.name(), "Communication controller");
+
+        let subclass = db.subclass(0x07, 0x00).unwrap();
+        assert_eq!(subclass.name(), "Serial controller");
+
+        let prog_if = subclass.prog_ifs().next().unwrap();
+        assert_eq!(prog_if.name(), "8250");
+    }
+
+    #[test]
+    fn test_from_reader_skips_unrecognized_trailing_sections() {
+        let sample = format!("{SAMPLE}\n# a comment\nsomething entirely unrecognized\n");
+        let db = PciIds::from_reader(sample.as_bytes()).unwrap();
+
+        assert!(db.vendor(0x14c3).is_some());
+        assert!(db.class(0x07).is_some());
+    }
+
+    #[test]
+    fn test_from_reader_errors_on_out_of_order_device_line() {
+        let sample = "\t1000  Orphan device\n";
+
+        let err = PciIds::from_reader(sample.as_bytes()).unwrap_err();
+
+        assert!(matches!(err, ParseError::UnexpectedLine(_)));
+    }
+}